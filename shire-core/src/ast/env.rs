@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+
+/// A variable binding in an [`Env`]. Unlike a plain `String`, this keeps the distinction
+/// `FrontMatterType` already models between scalar kinds and lists, so evaluators no longer
+/// have to round-trip booleans/numbers through their string representation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Str(String),
+    Number(i32),
+    Boolean(bool),
+    Date(String),
+    List(Vec<Value>),
+}
+
+impl Value {
+    /// The element count for `length`: characters for a string, items for a list.
+    pub fn len(&self) -> usize {
+        match self {
+            Value::Str(s) => s.chars().count(),
+            Value::List(items) => items.len(),
+            Value::Number(_) | Value::Boolean(_) | Value::Date(_) => 1,
+        }
+    }
+
+    /// `isEmpty`/`isNotEmpty` work the same way on lists as they do on strings.
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Value::Str(s) => s.is_empty(),
+            Value::List(items) => items.is_empty(),
+            Value::Number(_) | Value::Boolean(_) | Value::Date(_) => false,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Str(s) => Some(s),
+            Value::Date(d) => Some(d),
+            _ => None,
+        }
+    }
+}
+
+/// A typed replacement for `HashMap<String, String>` as the evaluator's variable environment.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Env {
+    values: HashMap<String, Value>,
+}
+
+impl Env {
+    pub fn new() -> Self {
+        Env { values: HashMap::new() }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.values.get(name)
+    }
+
+    pub fn insert(&mut self, name: impl Into<String>, value: Value) {
+        self.values.insert(name.into(), value);
+    }
+
+    /// Compatibility constructor for callers that still only have string-valued variables.
+    pub fn from_string_map(map: &HashMap<String, String>) -> Self {
+        Env {
+            values: map.iter().map(|(k, v)| (k.clone(), Value::Str(v.clone()))).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_returns_the_typed_value_it_was_inserted_with() {
+        let mut env = Env::new();
+        env.insert("count", Value::Number(3));
+        assert_eq!(env.get("count"), Some(&Value::Number(3)));
+        assert_eq!(env.get("missing"), None);
+    }
+
+    #[test]
+    fn from_string_map_wraps_every_entry_as_a_str_value() {
+        let mut map = HashMap::new();
+        map.insert("name".to_string(), "demo".to_string());
+        let env = Env::from_string_map(&map);
+        assert_eq!(env.get("name"), Some(&Value::Str("demo".to_string())));
+    }
+
+    #[test]
+    fn len_and_is_empty_treat_a_list_like_a_collection_not_a_scalar() {
+        let items = Value::List(vec![Value::Str("a".to_string()), Value::Str("b".to_string())]);
+        assert_eq!(items.len(), 2);
+        assert!(!items.is_empty());
+        assert!(Value::List(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn as_str_is_none_for_non_textual_values() {
+        assert_eq!(Value::Number(1).as_str(), None);
+        assert_eq!(Value::Str("x".to_string()).as_str(), Some("x"));
+        assert_eq!(Value::Date("2024-01-01".to_string()).as_str(), Some("2024-01-01"));
+    }
+}
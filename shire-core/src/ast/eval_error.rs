@@ -0,0 +1,42 @@
+use std::fmt;
+
+use crate::ast::typed_statement::ValueType;
+
+/// Every failure mode `Statement::evaluate` can hit, replacing the ad-hoc `Result<_, String>`
+/// and the handful of paths that used to `panic!`/`unwrap()` instead of reporting an error.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EvalError {
+    /// An operand did not have the type the operation required.
+    TypeMismatch { expected: ValueType, actual: String },
+    /// A `VARIABLE`/object reference that was not present in the environment.
+    UndefinedVariable(String),
+    /// A `MethodCall` whose method name this evaluator does not implement.
+    UnsupportedMethod(String),
+    /// `first`/`last` called on an empty string.
+    EmptyStringAccess,
+    /// A `matches`/`Matches` regex that failed to compile.
+    InvalidRegex { pattern: String, source: String },
+    /// An operand whose `FrontMatterType`/kind this operation cannot act on at all.
+    UnsupportedOperand(String),
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvalError::TypeMismatch { expected, actual } => {
+                write!(f, "expected a value of type {:?}, got {}", expected, actual)
+            }
+            EvalError::UndefinedVariable(name) => write!(f, "undefined variable: {}", name),
+            EvalError::UnsupportedMethod(name) => write!(f, "unsupported method: {}", name),
+            EvalError::EmptyStringAccess => write!(f, "cannot index into an empty string"),
+            EvalError::InvalidRegex { pattern, source } => {
+                write!(f, "invalid regex pattern \"{}\": {}", pattern, source)
+            }
+            EvalError::UnsupportedOperand(description) => {
+                write!(f, "unsupported operand: {}", description)
+            }
+        }
+    }
+}
+
+impl std::error::Error for EvalError {}
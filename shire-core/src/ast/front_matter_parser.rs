@@ -0,0 +1,448 @@
+use std::collections::HashMap;
+
+use nom::{
+    branch::alt,
+    bytes::complete::{is_not, tag, take_while1},
+    character::complete::{char, digit1, multispace0},
+    combinator::{map, map_res, opt, recognize},
+    multi::{fold_many0, separated_list0},
+    sequence::{delimited, preceded, tuple},
+    IResult,
+};
+
+use crate::ast::front_matter_type::{FrontMatterType, PipeOperator, PipelineStage, Processor, RuleBasedPatternAction, ShirePsiQueryStatement};
+use crate::ast::pattern_action_fun::VariableElement;
+use crate::ast::shire_expression::{Comparison, LogicalExpression, Operator, OperatorType, StatementType, Value};
+
+/// A byte range into the original front-matter source, attached to every parsed entry so
+/// tooling (a language server, a cache) can point back at exactly what produced a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// One recoverable problem found while parsing a single key, e.g. a malformed `PATTERN` or a
+/// line that isn't a `"key": value` entry at all. Recording this (instead of failing the
+/// whole header) is what lets one bad line coexist with an otherwise-valid front matter block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// The offending key, or `""` if the line could not even be parsed as `"key": ...`.
+    pub key: String,
+    pub message: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ParsedFrontMatter {
+    pub values: HashMap<String, FrontMatterType>,
+    pub spans: HashMap<String, Span>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// The byte offset of `sub` within `base`, relying on `sub` always being a slice nom peeled
+/// off of `base` (true for every intermediate `&str` this parser produces).
+fn offset(base: &str, sub: &str) -> usize {
+    sub.as_ptr() as usize - base.as_ptr() as usize
+}
+
+/// Consumes the rest of the current line (including the newline, if any). Never fails, so it
+/// is used for error recovery rather than as an `IResult` combinator.
+fn rest_of_line(input: &str) -> (&str, &str) {
+    match input.find('\n') {
+        Some(i) => (&input[i + 1..], &input[..i]),
+        None => ("", input),
+    }
+}
+
+fn parse_header_start(input: &str) -> IResult<&str, ()> {
+    let (input, _) = multispace0(input)?;
+    let (input, _) = tag("---")(input)?;
+    let (rest, _) = rest_of_line(input);
+    Ok((rest, ()))
+}
+
+fn parse_quoted_string(input: &str) -> IResult<&str, String> {
+    map(delimited(char('"'), is_not("\""), char('"')), |s: &str| s.to_string())(input)
+}
+
+fn parse_bool(input: &str) -> IResult<&str, bool> {
+    alt((map(tag("true"), |_| true), map(tag("false"), |_| false)))(input)
+}
+
+fn parse_integer(input: &str) -> IResult<&str, i32> {
+    map_res(recognize(tuple((opt(char('-')), digit1))), |s: &str| s.parse::<i32>())(input)
+}
+
+fn parse_identifier(input: &str) -> IResult<&str, &str> {
+    take_while1(|c: char| c.is_alphanumeric() || c == '_')(input)
+}
+
+/// A dotted reference such as `m.containingClass`, used for query `from`-bound variables.
+fn parse_dotted_identifier(input: &str) -> IResult<&str, String> {
+    map(take_while1(|c: char| c.is_alphanumeric() || c == '_' || c == '.'), |s: &str| s.to_string())(input)
+}
+
+fn parse_variable_ref(input: &str) -> IResult<&str, FrontMatterType> {
+    map(preceded(char('$'), parse_dotted_identifier), FrontMatterType::VARIABLE)(input)
+}
+
+fn parse_array(input: &str) -> IResult<&str, FrontMatterType> {
+    map(
+        delimited(
+            tuple((char('['), multispace0)),
+            separated_list0(tuple((multispace0, char(','), multispace0)), parse_value),
+            tuple((multispace0, char(']'))),
+        ),
+        FrontMatterType::ARRAY,
+    )(input)
+}
+
+fn parse_processor(input: &str) -> IResult<&str, Processor> {
+    let (input, func_name) = parse_identifier(input)?;
+    let (input, args) = opt(delimited(
+        char('('),
+        separated_list0(tuple((multispace0, char(','), multispace0)), parse_value),
+        char(')'),
+    ))(input)?;
+    Ok((input, Processor { func_name: func_name.to_string(), args: args.unwrap_or_default() }))
+}
+
+/// The combinator feeding the *next* stage: `|:` and `|?`/`|&` must be tried before the plain
+/// `|` tag, since `|` is a prefix of all three.
+fn parse_pipe_operator(input: &str) -> IResult<&str, PipeOperator> {
+    alt((
+        map(tag("|:"), |_| PipeOperator::Map),
+        map(tag("|?"), |_| PipeOperator::Filter),
+        map(tag("|&"), |_| PipeOperator::Merge),
+        map(tag("|"), |_| PipeOperator::Pipe),
+    ))(input)
+}
+
+/// `grep("error") | sort |: trim |? nonEmpty`: the first stage has no combinator of its own
+/// (an implicit `|`), every later stage is introduced by the `PipeOperator` that precedes it.
+fn parse_processor_chain(input: &str) -> IResult<&str, Vec<PipelineStage>> {
+    let (input, first) = parse_processor(input)?;
+    fold_many0(
+        tuple((multispace0, parse_pipe_operator, multispace0, parse_processor)),
+        move || vec![PipelineStage { operator: PipeOperator::Pipe, func: first.clone() }],
+        |mut stages, (_, operator, _, func)| {
+            stages.push(PipelineStage { operator, func });
+            stages
+        },
+    )(input)
+}
+
+/// `"*.java" -> grep("error") | sort`
+fn parse_pattern(input: &str) -> IResult<&str, FrontMatterType> {
+    let (input, pattern) = parse_quoted_string(input)?;
+    let (input, _) = delimited(multispace0, tag("->"), multispace0)(input)?;
+    let (input, processors) = parse_processor_chain(input)?;
+    Ok((input, FrontMatterType::PATTERN(RuleBasedPatternAction { pattern, processors })))
+}
+
+fn parse_case_body(input: &str) -> IResult<&str, Vec<PipelineStage>> {
+    delimited(tuple((char('{'), multispace0)), parse_processor_chain, tuple((multispace0, char('}'))))(input)
+}
+
+fn parse_case_arm(input: &str) -> IResult<&str, (String, Vec<PipelineStage>)> {
+    alt((
+        map(preceded(tuple((tag("default"), multispace0)), parse_case_body), |processors| {
+            ("default".to_string(), processors)
+        }),
+        map(tuple((parse_quoted_string, preceded(multispace0, parse_case_body))), |(key, processors)| (key, processors)),
+    ))(input)
+}
+
+/// `case "$0" { "error" { grep("ERROR") | sort } default { cat } }`
+fn parse_case_match(input: &str) -> IResult<&str, FrontMatterType> {
+    let (input, _) = tag("case")(input)?;
+    let (input, _) = delimited(multispace0, parse_quoted_string, multispace0)(input)?;
+    let (input, _) = tuple((char('{'), multispace0))(input)?;
+    let (input, cases) = fold_many0(
+        nom::sequence::terminated(parse_case_arm, multispace0),
+        HashMap::new,
+        |mut acc: HashMap<String, FrontMatterType>, (key, processors)| {
+            acc.insert(key.clone(), FrontMatterType::PATTERN(RuleBasedPatternAction { pattern: key, processors }));
+            acc
+        },
+    )(input)?;
+    let (input, _) = char('}')(input)?;
+    Ok((input, FrontMatterType::CASE_MATCH(cases)))
+}
+
+fn parse_variable_element(input: &str) -> IResult<&str, VariableElement> {
+    let (input, name) = parse_identifier(input)?;
+    let (input, _) = delimited(multispace0, char(':'), multispace0)(input)?;
+    let (input, kind) = parse_identifier(input)?;
+    Ok((input, VariableElement { name: name.to_string(), kind: kind.to_string() }))
+}
+
+fn parse_comparison_operator(input: &str) -> IResult<&str, OperatorType> {
+    alt((
+        map(tag("=="), |_| OperatorType::Equal),
+        map(tag("!="), |_| OperatorType::NotEqual),
+        map(tag("<="), |_| OperatorType::LessEqual),
+        map(tag(">="), |_| OperatorType::GreaterEqual),
+        map(tag("<"), |_| OperatorType::LessThan),
+        map(tag(">"), |_| OperatorType::GreaterThan),
+    ))(input)
+}
+
+fn parse_comparison_operand(input: &str) -> IResult<&str, FrontMatterType> {
+    alt((
+        map(parse_quoted_string, FrontMatterType::STRING),
+        map(parse_integer, FrontMatterType::NUMBER),
+        map(parse_dotted_identifier, FrontMatterType::VARIABLE),
+    ))(input)
+}
+
+fn parse_comparison(input: &str) -> IResult<&str, StatementType> {
+    let (input, left) = preceded(multispace0, parse_comparison_operand)(input)?;
+    let (input, operator) = delimited(multispace0, parse_comparison_operator, multispace0)(input)?;
+    let (input, right) = parse_comparison_operand(input)?;
+    Ok((
+        input,
+        StatementType::Comparison(Comparison { left: Box::new(left), operator: Operator { type_: operator }, right: Box::new(right) }),
+    ))
+}
+
+fn parse_logical_operator(input: &str) -> IResult<&str, OperatorType> {
+    alt((map(tag("&&"), |_| OperatorType::And), map(tag("||"), |_| OperatorType::Or)))(input)
+}
+
+/// A `where` clause: one or more comparisons, folded left-to-right by `&&`/`||`.
+fn parse_condition(input: &str) -> IResult<&str, StatementType> {
+    let (input, first) = parse_comparison(input)?;
+    fold_many0(
+        tuple((delimited(multispace0, parse_logical_operator, multispace0), parse_comparison)),
+        move || first.clone(),
+        |left, (operator, right)| {
+            StatementType::LogicalExpression(LogicalExpression { left: Box::new(left), operator, right: Box::new(right) })
+        },
+    )(input)
+}
+
+fn parse_select_item(input: &str) -> IResult<&str, StatementType> {
+    map(preceded(multispace0, parse_dotted_identifier), |name| {
+        StatementType::Value(Value { value: Box::new(FrontMatterType::VARIABLE(name)) })
+    })(input)
+}
+
+/// `from { m: Method, c: Class } where { m.containingClass == c } select m.name`
+fn parse_query_statement(input: &str) -> IResult<&str, FrontMatterType> {
+    let (input, _) = tag("from")(input)?;
+    let (input, from) = delimited(
+        tuple((multispace0, char('{'), multispace0)),
+        separated_list0(tuple((multispace0, char(','), multispace0)), parse_variable_element),
+        tuple((multispace0, char('}'))),
+    )(input)?;
+
+    let (input, _) = delimited(multispace0, tag("where"), multispace0)(input)?;
+    let (input, where_clause) = delimited(tuple((char('{'), multispace0)), parse_condition, tuple((multispace0, char('}'))))(input)?;
+
+    let (input, _) = delimited(multispace0, tag("select"), multispace0)(input)?;
+    let (input, select) = separated_list0(tuple((multispace0, char(','), multispace0)), parse_select_item)(input)?;
+
+    Ok((
+        input,
+        FrontMatterType::QUERY_STATEMENT(ShirePsiQueryStatement { from, where_clause: Box::new(where_clause), select }),
+    ))
+}
+
+fn parse_value(input: &str) -> IResult<&str, FrontMatterType> {
+    alt((
+        parse_query_statement,
+        parse_case_match,
+        parse_pattern,
+        parse_variable_ref,
+        parse_array,
+        map(parse_quoted_string, FrontMatterType::STRING),
+        map(parse_bool, FrontMatterType::BOOLEAN),
+        map(parse_integer, FrontMatterType::NUMBER),
+    ))(input)
+}
+
+fn parse_key(input: &str) -> IResult<&str, String> {
+    let (input, key) = preceded(multispace0, parse_quoted_string)(input)?;
+    let (input, _) = delimited(multispace0, char(':'), multispace0)(input)?;
+    Ok((input, key))
+}
+
+/// Parses a `---\n ... \n---` front-matter block into `FrontMatterType` values, recovering
+/// from a malformed entry instead of discarding the whole header: a key whose value fails to
+/// parse is recorded as `FrontMatterType::ERROR` (with a matching `Diagnostic`), and parsing
+/// continues with the next line.
+pub fn parse_front_matter(source: &str) -> ParsedFrontMatter {
+    let mut result = ParsedFrontMatter::default();
+
+    let mut rest = match parse_header_start(source) {
+        Ok((rest, _)) => rest,
+        Err(_) => return result,
+    };
+
+    loop {
+        let (trimmed, _) = multispace0::<_, nom::error::Error<&str>>(rest).unwrap();
+        rest = trimmed;
+        if rest.is_empty() || rest.starts_with("---") {
+            break;
+        }
+
+        let entry_start = offset(source, rest);
+        match parse_key(rest) {
+            Ok((after_key, key)) => match parse_value(after_key) {
+                Ok((after_value, value)) => {
+                    let span = Span { start: entry_start, end: offset(source, after_value) };
+                    result.values.insert(key.clone(), value);
+                    result.spans.insert(key, span);
+                    rest = after_value;
+                }
+                Err(_) => {
+                    let (after_line, _) = rest_of_line(after_key);
+                    let span = Span { start: entry_start, end: offset(source, after_line) };
+                    let message = format!("could not parse value for \"{}\"", key);
+                    result.values.insert(key.clone(), FrontMatterType::ERROR(message.clone()));
+                    result.spans.insert(key.clone(), span);
+                    result.diagnostics.push(Diagnostic { key, message, span });
+                    rest = after_line;
+                }
+            },
+            Err(_) => {
+                let (after_line, _) = rest_of_line(rest);
+                if after_line == rest {
+                    break;
+                }
+                result.diagnostics.push(Diagnostic {
+                    key: String::new(),
+                    message: "expected a `\"key\": value` entry".to_string(),
+                    span: Span { start: entry_start, end: offset(source, after_line) },
+                });
+                rest = after_line;
+            }
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_plain_scalars() {
+        let input = "---\n\"name\": \"demo\"\n\"count\": 3\n\"enabled\": true\n---\n";
+        let result = parse_front_matter(input);
+        assert_eq!(result.values.get("name"), Some(&FrontMatterType::STRING("demo".to_string())));
+        assert_eq!(result.values.get("count"), Some(&FrontMatterType::NUMBER(3)));
+        assert_eq!(result.values.get("enabled"), Some(&FrontMatterType::BOOLEAN(true)));
+        assert!(result.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn parses_variable_and_pattern() {
+        let input = "---\n\"target\": $name\n\"files\": \"*.java\" -> grep(\"error\") | sort\n---\n";
+        let result = parse_front_matter(input);
+        assert_eq!(result.values.get("target"), Some(&FrontMatterType::VARIABLE("name".to_string())));
+        match result.values.get("files") {
+            Some(FrontMatterType::PATTERN(action)) => {
+                assert_eq!(action.pattern, "*.java");
+                assert_eq!(action.processors.len(), 2);
+            }
+            other => panic!("expected a PATTERN, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn recovers_from_a_malformed_entry() {
+        let input = "---\n\"good\": \"ok\"\n\"bad\": ???\n\"also_good\": 1\n---\n";
+        let result = parse_front_matter(input);
+        assert_eq!(result.values.get("good"), Some(&FrontMatterType::STRING("ok".to_string())));
+        assert_eq!(result.values.get("also_good"), Some(&FrontMatterType::NUMBER(1)));
+        assert!(matches!(result.values.get("bad"), Some(FrontMatterType::ERROR(_))));
+        assert_eq!(result.diagnostics.len(), 1);
+        assert_eq!(result.diagnostics[0].key, "bad");
+    }
+
+    #[test]
+    fn parses_and_runs_a_pattern_pipeline() {
+        use crate::ast::front_matter_type::ProcessorRegistry;
+
+        let input = "---\n\"files\": \"*.java\" -> grep(\"error\") | sort\n---\n";
+        let result = parse_front_matter(input);
+        let action = match result.values.get("files") {
+            Some(FrontMatterType::PATTERN(action)) => action.clone(),
+            other => panic!("expected a PATTERN, got {:?}", other),
+        };
+
+        let input_value = FrontMatterType::ARRAY(vec![
+            FrontMatterType::STRING("b error".to_string()),
+            FrontMatterType::STRING("a ok".to_string()),
+            FrontMatterType::STRING("a error".to_string()),
+        ]);
+        let output = action.run(input_value, &ProcessorRegistry::new()).expect("pattern should run");
+        assert_eq!(
+            output,
+            FrontMatterType::ARRAY(vec![
+                FrontMatterType::STRING("a error".to_string()),
+                FrontMatterType::STRING("b error".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn parses_and_runs_the_map_and_filter_combinators() {
+        use crate::ast::front_matter_type::ProcessorRegistry;
+
+        let mut registry = ProcessorRegistry::new();
+        registry.register("upper", Box::new(|input, _args| match input {
+            FrontMatterType::STRING(s) => Ok(FrontMatterType::STRING(s.to_uppercase())),
+            other => Ok(other),
+        }));
+        registry.register("nonEmpty", Box::new(|input, _args| {
+            let keep = !matches!(&input, FrontMatterType::STRING(s) if s.is_empty());
+            Ok(FrontMatterType::BOOLEAN(keep))
+        }));
+
+        let input = "---\n\"upper\": \"*.java\" -> grep(\"a\") |: upper\n---\n";
+        let result = parse_front_matter(input);
+        let action = match result.values.get("upper") {
+            Some(FrontMatterType::PATTERN(action)) => action.clone(),
+            other => panic!("expected a PATTERN, got {:?}", other),
+        };
+        assert_eq!(action.processors[1].operator, PipeOperator::Map);
+        let input_value = FrontMatterType::ARRAY(vec![
+            FrontMatterType::STRING("a line".to_string()),
+            FrontMatterType::STRING("b line".to_string()),
+        ]);
+        let output = action.run(input_value, &registry).expect("pattern should run");
+        assert_eq!(output, FrontMatterType::ARRAY(vec![FrontMatterType::STRING("A LINE".to_string())]));
+
+        let input = "---\n\"kept\": \"*.java\" -> grep(\"a\") |? nonEmpty\n---\n";
+        let result = parse_front_matter(input);
+        let action = match result.values.get("kept") {
+            Some(FrontMatterType::PATTERN(action)) => action.clone(),
+            other => panic!("expected a PATTERN, got {:?}", other),
+        };
+        assert_eq!(action.processors[1].operator, PipeOperator::Filter);
+        let input_value = FrontMatterType::ARRAY(vec![
+            FrontMatterType::STRING("a line".to_string()),
+            FrontMatterType::STRING("b line".to_string()),
+        ]);
+        let output = action.run(input_value, &registry).expect("pattern should run");
+        assert_eq!(output, FrontMatterType::ARRAY(vec![FrontMatterType::STRING("a line".to_string())]));
+    }
+
+    #[test]
+    fn parses_a_query_statement() {
+        let input = "---\n\"matches\": from { m: Method } where { m.name == \"run\" } select m.name\n---\n";
+        let result = parse_front_matter(input);
+        match result.values.get("matches") {
+            Some(FrontMatterType::QUERY_STATEMENT(query)) => {
+                assert_eq!(query.from.len(), 1);
+                assert_eq!(query.select.len(), 1);
+            }
+            other => panic!("expected a QUERY_STATEMENT, got {:?}", other),
+        }
+    }
+}
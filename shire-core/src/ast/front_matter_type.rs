@@ -1,8 +1,10 @@
 use std::collections::HashMap;
 use std::fmt;
 use std::fmt::Debug;
+use crate::ast::env::{Env, Value as EnvValue};
 use crate::ast::pattern_action_fun::VariableElement;
 use crate::ast::shire_expression::{Statement, StatementType};
+use crate::ast::typed_statement::ValueType;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum FrontMatterType {
@@ -42,16 +44,11 @@ impl FrontMatterType {
                     .collect();
                 format!("{{{}}}", elements.join(", "))
             }
-            FrontMatterType::PATTERN(value) => format!("{} -> {}", value.pattern, value.processors.iter().map(|p| p.func_name.clone()).collect::<Vec<_>>().join(", ")),
+            FrontMatterType::PATTERN(value) => format!("{} -> {}", value.pattern, display_stages(&value.processors)),
             FrontMatterType::CASE_MATCH(value) => {
                 let elements: Vec<String> = value.iter().map(|(k, v)| {
-                    let pattern = if let FrontMatterType::PATTERN(pattern) = v {
-                        pattern.pattern.clone()
-                    } else {
-                        "".to_string()
-                    };
                     let processors = if let FrontMatterType::PATTERN(pattern) = v {
-                        pattern.processors.iter().map(|p| p.func_name.clone()).collect::<Vec<_>>().join(" | ")
+                        display_stages(&pattern.processors)
                     } else {
                         "".to_string()
                     };
@@ -67,43 +64,521 @@ impl FrontMatterType {
         }
     }
 
-    // to_value 方法实现
-    pub fn to_value(&self) -> &dyn std::any::Any {
+    /// Infers the type this node will produce once evaluated, without running it, so the
+    /// compiler can reject front matter like `priority: "high" + 3` or a mixed-element array
+    /// before execution instead of failing at runtime.
+    pub fn expected_type(&self, env: &HashMap<String, FrontMatterValueType>) -> Option<FrontMatterValueType> {
+        match self {
+            FrontMatterType::STRING(_) => Some(FrontMatterValueType::String),
+            FrontMatterType::NUMBER(_) => Some(FrontMatterValueType::Number),
+            FrontMatterType::DATE(_) => Some(FrontMatterValueType::Date),
+            FrontMatterType::BOOLEAN(_) => Some(FrontMatterValueType::Boolean),
+            FrontMatterType::ERROR(_) => None,
+            FrontMatterType::EMPTY => Some(FrontMatterValueType::Unknown),
+            FrontMatterType::ARRAY(elements) => {
+                // An empty array (`"tags": []`) is a legitimate, if uninformative, value — it
+                // shouldn't read as a type error just because there's no element to infer from.
+                let Some(last) = elements.last() else {
+                    return Some(FrontMatterValueType::Array(Box::new(FrontMatterValueType::Unknown)));
+                };
+                let last = last.expected_type(env)?;
+                let homogeneous = elements.iter().all(|e| e.expected_type(env).as_ref() == Some(&last));
+                if homogeneous {
+                    Some(FrontMatterValueType::Array(Box::new(last)))
+                } else {
+                    None
+                }
+            }
+            FrontMatterType::OBJECT(_) => Some(FrontMatterValueType::Object),
+            FrontMatterType::PATTERN(_) => Some(FrontMatterValueType::Pattern),
+            FrontMatterType::CASE_MATCH(_) => Some(FrontMatterValueType::Object),
+            FrontMatterType::VARIABLE(name) | FrontMatterType::IDENTIFIER(name) => env.get(name).cloned(),
+            FrontMatterType::EXPRESSION(statement) => statement_expected_type(statement),
+            // `ShirePsiQueryStatement::execute` returns one `HashMap<String, FrontMatterType>`
+            // row per match (one entry per `select` column), not a flat array of the last
+            // select column's type — match that shape here instead of disagreeing with it.
+            FrontMatterType::QUERY_STATEMENT(_) => Some(FrontMatterValueType::Array(Box::new(FrontMatterValueType::Object))),
+        }
+    }
+
+    /// Renders this value as JSON text. `ARRAY`/`OBJECT`/`CASE_MATCH` map onto sequences and
+    /// objects as expected; kinds with no JSON equivalent (`PATTERN`, `EXPRESSION`,
+    /// `QUERY_STATEMENT`, ...) are emitted as a `{"type": ..., "source": ...}` tagged object
+    /// carrying their Shire source form instead of losing information the way `&dyn Any` did.
+    /// There is no matching reader: JSON is not parsed back into a `FrontMatterType`.
+    pub fn to_json(&self) -> String {
         match self {
-            FrontMatterType::STRING(value) => value,
-            FrontMatterType::NUMBER(value) => value,
-            FrontMatterType::DATE(value) => value,
-            FrontMatterType::BOOLEAN(value) => value,
-            FrontMatterType::ERROR(value) => value,
-            FrontMatterType::EMPTY => &"",
-            FrontMatterType::ARRAY(value) => value,
-            FrontMatterType::OBJECT(value) => value,
-            FrontMatterType::PATTERN(value) => value,
-            FrontMatterType::CASE_MATCH(value) => value,
-            FrontMatterType::VARIABLE(value) => value,
-            FrontMatterType::EXPRESSION(statement) => statement,
-            FrontMatterType::IDENTIFIER(value) => value,
-            FrontMatterType::QUERY_STATEMENT(query_statement) => query_statement,
+            FrontMatterType::STRING(value) => json_quote(value),
+            FrontMatterType::NUMBER(value) => value.to_string(),
+            FrontMatterType::DATE(value) => json_quote(value),
+            FrontMatterType::BOOLEAN(value) => value.to_string(),
+            FrontMatterType::ERROR(value) => tagged_json("error", value),
+            FrontMatterType::EMPTY => "null".to_string(),
+            FrontMatterType::ARRAY(values) => {
+                let elements: Vec<String> = values.iter().map(|v| v.to_json()).collect();
+                format!("[{}]", elements.join(","))
+            }
+            FrontMatterType::OBJECT(map) | FrontMatterType::CASE_MATCH(map) => {
+                format!("{{{}}}", sorted_fields(map, |v| v.to_json()).join(","))
+            }
+            FrontMatterType::PATTERN(_) => tagged_json("pattern", &self.display()),
+            FrontMatterType::VARIABLE(name) => tagged_json("variable", name),
+            FrontMatterType::EXPRESSION(_) => tagged_json("expression", &self.display()),
+            FrontMatterType::IDENTIFIER(name) => tagged_json("identifier", name),
+            FrontMatterType::QUERY_STATEMENT(_) => tagged_json("query", &self.display()),
+        }
+    }
+
+    /// Renders this value as YAML text. The same kinds that have no native YAML shape are
+    /// emitted using a YAML tag (`!pattern`, `!expression`, ...) wrapping their source form.
+    pub fn to_yaml(&self) -> String {
+        self.to_yaml_indented(0)
+    }
+
+    fn to_yaml_indented(&self, indent: usize) -> String {
+        let pad = "  ".repeat(indent);
+        match self {
+            FrontMatterType::STRING(value) => yaml_quote(value),
+            FrontMatterType::NUMBER(value) => value.to_string(),
+            FrontMatterType::DATE(value) => value.to_string(),
+            FrontMatterType::BOOLEAN(value) => value.to_string(),
+            FrontMatterType::ERROR(value) => format!("!error {}", yaml_quote(value)),
+            FrontMatterType::EMPTY => "null".to_string(),
+            FrontMatterType::ARRAY(values) => {
+                if values.is_empty() {
+                    "[]".to_string()
+                } else {
+                    values
+                        .iter()
+                        .map(|v| format!("\n{}- {}", pad, v.to_yaml_indented(indent + 1)))
+                        .collect::<Vec<_>>()
+                        .join("")
+                }
+            }
+            FrontMatterType::OBJECT(map) | FrontMatterType::CASE_MATCH(map) => {
+                if map.is_empty() {
+                    "{}".to_string()
+                } else {
+                    let mut entries: Vec<(&String, &FrontMatterType)> = map.iter().collect();
+                    entries.sort_by(|a, b| a.0.cmp(b.0));
+                    entries
+                        .into_iter()
+                        .map(|(k, v)| format!("\n{}{}: {}", pad, k, v.to_yaml_indented(indent + 1)))
+                        .collect::<Vec<_>>()
+                        .join("")
+                }
+            }
+            FrontMatterType::PATTERN(_) => format!("!pattern {}", yaml_quote(&self.display())),
+            FrontMatterType::VARIABLE(name) => format!("!variable {}", yaml_quote(name)),
+            FrontMatterType::EXPRESSION(_) => format!("!expression {}", yaml_quote(&self.display())),
+            FrontMatterType::IDENTIFIER(name) => format!("!identifier {}", yaml_quote(name)),
+            FrontMatterType::QUERY_STATEMENT(_) => format!("!query {}", yaml_quote(&self.display())),
+        }
+    }
+}
+
+/// Escapes `value` so it can be embedded in a double-quoted JSON/YAML scalar: `\` and `"` are
+/// backslash-escaped, and every C0 control character (`U+0000`..=`U+001F`) is escaped too — a
+/// raw control character (e.g. an unescaped tab or a stray `\0`) is not legal inside a
+/// double-quoted string in either format. The common named escapes match both formats; anything
+/// else in that range falls back to a `\u00XX` code point escape, which both formats also accept.
+fn escape_quoted(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            '\u{0000}'..='\u{001F}' => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            _ => escaped.push(c),
         }
     }
+    escaped
+}
+
+fn json_quote(value: &str) -> String {
+    format!("\"{}\"", escape_quoted(value))
+}
+
+fn yaml_quote(value: &str) -> String {
+    format!("\"{}\"", escape_quoted(value))
+}
+
+fn tagged_json(kind: &str, source: &str) -> String {
+    format!("{{\"type\":{},\"source\":{}}}", json_quote(kind), json_quote(source))
+}
+
+/// Renders a `FrontMatterType` map's entries as `"key":<rendered value>` pairs, sorted by key
+/// so output is deterministic despite `HashMap`'s unspecified iteration order.
+fn sorted_fields(map: &HashMap<String, FrontMatterType>, render: impl Fn(&FrontMatterType) -> String) -> Vec<String> {
+    let mut entries: Vec<(&String, &FrontMatterType)> = map.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries.into_iter().map(|(k, v)| format!("{}:{}", json_quote(k), render(v))).collect()
+}
+
+/// The type a [`FrontMatterType`] node will evaluate to, inferred ahead of execution —
+/// mirrors the approach in dust-lang where each AST node reports its own result type.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum FrontMatterValueType {
+    String,
+    Number,
+    Date,
+    Boolean,
+    Array(Box<FrontMatterValueType>),
+    Object,
+    Pattern,
+    Query,
+    Unknown,
+}
+
+/// Infers the type a condition/expression statement evaluates to by running the existing
+/// type-check pass and translating its result, rather than duplicating that logic here.
+fn statement_expected_type(statement: &StatementType) -> Option<FrontMatterValueType> {
+    let typed = statement.check().ok()?;
+    let ty = typed.ty()?;
+    Some(match ty {
+        ValueType::Bool => FrontMatterValueType::Boolean,
+        ValueType::Str => FrontMatterValueType::String,
+        ValueType::Number => FrontMatterValueType::Number,
+        ValueType::Date => FrontMatterValueType::Date,
+    })
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct RuleBasedPatternAction {
-    pattern: String,
-    processors: Vec<Processor>,
+    pub(crate) pattern: String,
+    pub(crate) processors: Vec<PipelineStage>,
+}
+
+impl RuleBasedPatternAction {
+    /// Threads `input` left-to-right through this pattern's piped processor chain, e.g.
+    /// `"*.java" -> grep("error") | sort |: trim`, dispatching each stage on its own
+    /// [`PipeOperator`] rather than always calling straight through, and short-circuiting on
+    /// the first stage that errors (an unknown function name or an arity mismatch).
+    pub fn run(&self, input: FrontMatterType, registry: &ProcessorRegistry) -> Result<FrontMatterType, String> {
+        let mut value = input;
+        for stage in &self.processors {
+            value = stage.run(value, registry)?;
+        }
+        Ok(value)
+    }
+}
+
+/// Joins a pipeline's stages back into their source form: the first stage shows only its call,
+/// every later stage is prefixed with the combinator that feeds it (`|`, `|:`, `|?`, `|&`).
+fn display_stages(stages: &[PipelineStage]) -> String {
+    stages
+        .iter()
+        .enumerate()
+        .map(|(i, stage)| {
+            if i == 0 {
+                stage.func.display()
+            } else {
+                format!("{} {}", stage.operator.display(), stage.func.display())
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Processor {
-    func_name: String,
+    pub(crate) func_name: String,
+    pub(crate) args: Vec<FrontMatterType>,
+}
+
+impl Processor {
+    fn display(&self) -> String {
+        if self.args.is_empty() {
+            self.func_name.clone()
+        } else {
+            let args = self.args.iter().map(|arg| arg.display()).collect::<Vec<_>>().join(", ");
+            format!("{}({})", self.func_name, args)
+        }
+    }
+}
+
+/// The combinator feeding a [`PipelineStage`], borrowed from stream-pipeline DSLs.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum PipeOperator {
+    /// `|` feeds the whole value to the next stage.
+    Pipe,
+    /// `|:` applies the next stage element-wise over a collection value, flattening the
+    /// per-element results back into one list.
+    Map,
+    /// `|?` keeps only the elements for which the next stage's result is truthy.
+    Filter,
+    /// `|&` runs the next stage over the whole current value, then unions its result into the
+    /// current value (deduplicating elements already present).
+    Merge,
+}
+
+impl PipeOperator {
+    fn display(&self) -> &'static str {
+        match self {
+            PipeOperator::Pipe => "|",
+            PipeOperator::Map => "|:",
+            PipeOperator::Filter => "|?",
+            PipeOperator::Merge => "|&",
+        }
+    }
+}
+
+/// One stage in a pattern-action pipeline: the processor call to run, and the combinator that
+/// decides how its result folds into the value threaded from the previous stage.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PipelineStage {
+    pub(crate) operator: PipeOperator,
+    pub(crate) func: Processor,
+}
+
+/// Whether a processor's result should be treated as "kept" by a `|?` filter stage: an empty
+/// string/array/object is falsy, `BOOLEAN(false)` and `EMPTY` are falsy, everything else is truthy.
+fn is_truthy(value: &FrontMatterType) -> bool {
+    match value {
+        FrontMatterType::BOOLEAN(b) => *b,
+        FrontMatterType::STRING(s) => !s.is_empty(),
+        FrontMatterType::ARRAY(items) => !items.is_empty(),
+        FrontMatterType::OBJECT(map) => !map.is_empty(),
+        FrontMatterType::EMPTY => false,
+        _ => true,
+    }
+}
+
+impl PipelineStage {
+    /// Runs this single stage over `value`, applying `self.operator`'s combinator semantics
+    /// around the plain `self.func` call.
+    fn run(&self, value: FrontMatterType, registry: &ProcessorRegistry) -> Result<FrontMatterType, String> {
+        match self.operator {
+            PipeOperator::Pipe => registry.call(&self.func.func_name, value, &self.func.args),
+            PipeOperator::Map => {
+                let mut mapped = Vec::new();
+                for line in as_lines(&value) {
+                    let result = registry.call(&self.func.func_name, FrontMatterType::STRING(line), &self.func.args)?;
+                    mapped.extend(as_lines(&result).into_iter().map(FrontMatterType::STRING));
+                }
+                Ok(FrontMatterType::ARRAY(mapped))
+            }
+            PipeOperator::Filter => {
+                let mut kept = Vec::new();
+                for line in as_lines(&value) {
+                    let result = registry.call(&self.func.func_name, FrontMatterType::STRING(line.clone()), &self.func.args)?;
+                    if is_truthy(&result) {
+                        kept.push(FrontMatterType::STRING(line));
+                    }
+                }
+                Ok(FrontMatterType::ARRAY(kept))
+            }
+            PipeOperator::Merge => {
+                let left = as_lines(&value);
+                let right = registry.call(&self.func.func_name, value.clone(), &self.func.args)?;
+                let mut merged = left.clone();
+                for line in as_lines(&right) {
+                    if !merged.contains(&line) {
+                        merged.push(line);
+                    }
+                }
+                Ok(FrontMatterType::ARRAY(merged.into_iter().map(FrontMatterType::STRING).collect()))
+            }
+        }
+    }
+}
+
+/// A built-in or user-registered processor function: takes the pipeline's current value plus
+/// this stage's call arguments, producing the value the next stage sees.
+pub type ProcessorFn = dyn Fn(FrontMatterType, &[FrontMatterType]) -> Result<FrontMatterType, String> + Send + Sync;
+
+/// Maps processor names (`grep`, `sort`, ...) to the handler that runs them, so
+/// `RuleBasedPatternAction::run` doesn't need a hardcoded match on function name.
+pub struct ProcessorRegistry {
+    handlers: HashMap<String, Box<ProcessorFn>>,
+}
+
+impl ProcessorRegistry {
+    /// A registry pre-populated with the built-in `grep`/`sort`/`xargs`/`cat` processors.
+    pub fn new() -> Self {
+        let mut handlers: HashMap<String, Box<ProcessorFn>> = HashMap::new();
+        handlers.insert("grep".to_string(), Box::new(builtin_grep));
+        handlers.insert("sort".to_string(), Box::new(builtin_sort));
+        handlers.insert("xargs".to_string(), Box::new(builtin_xargs));
+        handlers.insert("cat".to_string(), Box::new(builtin_cat));
+        ProcessorRegistry { handlers }
+    }
+
+    /// Registers or overrides a processor function under `name`.
+    pub fn register(&mut self, name: impl Into<String>, handler: Box<ProcessorFn>) {
+        self.handlers.insert(name.into(), handler);
+    }
+
+    fn call(&self, name: &str, input: FrontMatterType, args: &[FrontMatterType]) -> Result<FrontMatterType, String> {
+        let handler = self.handlers.get(name).ok_or_else(|| format!("unknown processor function: {}", name))?;
+        handler(input, args)
+    }
+}
+
+impl Default for ProcessorRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Flattens a value into the lines a text-oriented processor operates over: an `ARRAY`
+/// becomes one line per element, a `STRING` is split on newlines, anything else becomes a
+/// single line of its display text.
+fn as_lines(value: &FrontMatterType) -> Vec<String> {
+    match value {
+        FrontMatterType::ARRAY(items) => items.iter().map(text_value).collect(),
+        FrontMatterType::STRING(s) => s.lines().map(str::to_string).collect(),
+        other => vec![text_value(other)],
+    }
+}
+
+/// Like `display()`, but without the surrounding quotes `display()` adds for `STRING`.
+fn text_value(value: &FrontMatterType) -> String {
+    match value {
+        FrontMatterType::STRING(s) => s.clone(),
+        other => other.display(),
+    }
+}
+
+fn builtin_grep(input: FrontMatterType, args: &[FrontMatterType]) -> Result<FrontMatterType, String> {
+    let pattern = args.first().map(text_value).ok_or_else(|| "grep requires a pattern argument".to_string())?;
+    let matched = as_lines(&input)
+        .into_iter()
+        .filter(|line| line.contains(&pattern))
+        .map(FrontMatterType::STRING)
+        .collect();
+    Ok(FrontMatterType::ARRAY(matched))
+}
+
+fn builtin_sort(input: FrontMatterType, _args: &[FrontMatterType]) -> Result<FrontMatterType, String> {
+    let mut lines = as_lines(&input);
+    lines.sort();
+    Ok(FrontMatterType::ARRAY(lines.into_iter().map(FrontMatterType::STRING).collect()))
+}
+
+fn builtin_xargs(input: FrontMatterType, args: &[FrontMatterType]) -> Result<FrontMatterType, String> {
+    let command = args.first().map(text_value).ok_or_else(|| "xargs requires a command argument".to_string())?;
+    let lines = as_lines(&input)
+        .into_iter()
+        .map(|line| FrontMatterType::STRING(format!("{} {}", command, line)))
+        .collect();
+    Ok(FrontMatterType::ARRAY(lines))
+}
+
+fn builtin_cat(input: FrontMatterType, args: &[FrontMatterType]) -> Result<FrontMatterType, String> {
+    let mut lines = as_lines(&input);
+    for arg in args {
+        lines.extend(as_lines(arg));
+    }
+    Ok(FrontMatterType::STRING(lines.join("\n")))
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct ShirePsiQueryStatement {
-    from: Vec<VariableElement>,
-    where_clause: Box<StatementType>,
-    select: Vec<StatementType>,
+    pub(crate) from: Vec<VariableElement>,
+    pub(crate) where_clause: Box<StatementType>,
+    pub(crate) select: Vec<StatementType>,
+}
+
+/// Supplies the candidate entities a [`ShirePsiQueryStatement`] binds its `from` variables to.
+/// Each entity is a flat field map (e.g. `name`, `containingClass`) keyed by field name.
+pub trait QuerySource {
+    fn entities_of_kind(&self, kind: &str) -> Vec<HashMap<String, FrontMatterType>>;
+}
+
+/// Converts a `FrontMatterType` literal into the `Env`'s typed runtime representation;
+/// `None` for kinds (patterns, queries, expressions, ...) that have no variable-value form.
+fn front_matter_to_env_value(value: &FrontMatterType) -> Option<EnvValue> {
+    match value {
+        FrontMatterType::STRING(s) => Some(EnvValue::Str(s.clone())),
+        FrontMatterType::NUMBER(n) => Some(EnvValue::Number(*n)),
+        FrontMatterType::DATE(d) => Some(EnvValue::Date(d.clone())),
+        FrontMatterType::BOOLEAN(b) => Some(EnvValue::Boolean(*b)),
+        FrontMatterType::ARRAY(items) => {
+            let values: Option<Vec<EnvValue>> = items.iter().map(front_matter_to_env_value).collect();
+            values.map(EnvValue::List)
+        }
+        _ => None,
+    }
+}
+
+/// Decodes a projected `select` result back into a `FrontMatterType`, matching the same
+/// downcast-by-variant approach `Statement::evaluate` callers already use elsewhere.
+fn any_to_front_matter(value: &dyn std::any::Any) -> FrontMatterType {
+    if let Some(s) = value.downcast_ref::<String>() {
+        FrontMatterType::STRING(s.clone())
+    } else if let Some(n) = value.downcast_ref::<i32>() {
+        FrontMatterType::NUMBER(*n)
+    } else if let Some(b) = value.downcast_ref::<bool>() {
+        FrontMatterType::BOOLEAN(*b)
+    } else {
+        FrontMatterType::EMPTY
+    }
+}
+
+impl ShirePsiQueryStatement {
+    /// Evaluates this query against `ctx`. Each `from` variable is bound to its candidate
+    /// entities, flattened into `"var.field"` env entries; `where_clause` is then checked
+    /// against every combination as a nested-loop join, so an equi-join predicate like
+    /// `m.containingClass == c.name` only survives for combinations where both sides agree.
+    /// Surviving rows are projected through `select`.
+    pub fn execute(&self, ctx: &impl QuerySource) -> Vec<HashMap<String, FrontMatterType>> {
+        if self.from.is_empty() {
+            return Vec::new();
+        }
+
+        let mut rows: Vec<Env> = vec![Env::new()];
+        for element in &self.from {
+            let mut next_rows = Vec::new();
+            for entity in ctx.entities_of_kind(&element.kind) {
+                for row in &rows {
+                    let mut candidate_row = row.clone();
+                    for (field, value) in &entity {
+                        if let Some(env_value) = front_matter_to_env_value(value) {
+                            candidate_row.insert(format!("{}.{}", element.name, field), env_value);
+                        }
+                    }
+                    next_rows.push(candidate_row);
+                }
+            }
+            rows = next_rows;
+        }
+
+        rows.into_iter()
+            .filter(|row| {
+                matches!(
+                    self.where_clause.as_ref().evaluate(row).ok().and_then(|result| result.downcast_ref::<bool>().copied()),
+                    Some(true)
+                )
+            })
+            .map(|row| self.project(&row))
+            .collect()
+    }
+
+    fn project(&self, row: &Env) -> HashMap<String, FrontMatterType> {
+        self.select
+            .iter()
+            .map(|statement| {
+                // A bare `select m.name` column is keyed by the name as written, without the `$`
+                // that `FrontMatterType::VARIABLE`'s display adds.
+                let column = match statement {
+                    StatementType::Value(value) => match value.value.as_ref() {
+                        FrontMatterType::VARIABLE(name) => name.clone(),
+                        _ => statement.display(),
+                    },
+                    _ => statement.display(),
+                };
+                let value = statement
+                    .evaluate(row)
+                    .map(|boxed| any_to_front_matter(boxed.as_ref()))
+                    .unwrap_or(FrontMatterType::EMPTY);
+                (column, value)
+            })
+            .collect()
+    }
 }
 
 impl fmt::Display for ShirePsiQueryStatement {
@@ -118,4 +593,140 @@ impl fmt::Display for ShirePsiQueryStatement {
             select_str
         )
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::shire_expression::{Comparison, Operator, OperatorType, Value};
+
+    struct FixtureSource;
+
+    impl QuerySource for FixtureSource {
+        fn entities_of_kind(&self, kind: &str) -> Vec<HashMap<String, FrontMatterType>> {
+            match kind {
+                "Method" => vec![
+                    HashMap::from([
+                        ("name".to_string(), FrontMatterType::STRING("run".to_string())),
+                        ("containingClass".to_string(), FrontMatterType::STRING("Main".to_string())),
+                    ]),
+                    HashMap::from([
+                        ("name".to_string(), FrontMatterType::STRING("helper".to_string())),
+                        ("containingClass".to_string(), FrontMatterType::STRING("Util".to_string())),
+                    ]),
+                ],
+                "Class" => vec![
+                    HashMap::from([("name".to_string(), FrontMatterType::STRING("Main".to_string()))]),
+                    HashMap::from([("name".to_string(), FrontMatterType::STRING("Util".to_string()))]),
+                ],
+                _ => Vec::new(),
+            }
+        }
+    }
+
+    fn equality(left: &str, right: &str) -> StatementType {
+        StatementType::Comparison(Comparison {
+            left: Box::new(FrontMatterType::VARIABLE(left.to_string())),
+            operator: Operator { type_: OperatorType::Equal },
+            right: Box::new(FrontMatterType::VARIABLE(right.to_string())),
+        })
+    }
+
+    /// `from { m: Method, c: Class } where { m.containingClass == c.name } select m.name` joined
+    /// against `FixtureSource` should keep exactly the `(m, c)` combinations where the equi-join
+    /// predicate actually holds, i.e. `m: run/Main` paired with `c: Main` and `m: helper/Util`
+    /// paired with `c: Util`, in that order.
+    #[test]
+    fn execute_joins_from_variables_on_the_where_clause() {
+        let query = ShirePsiQueryStatement {
+            from: vec![
+                VariableElement { name: "m".to_string(), kind: "Method".to_string() },
+                VariableElement { name: "c".to_string(), kind: "Class".to_string() },
+            ],
+            where_clause: Box::new(equality("m.containingClass", "c.name")),
+            select: vec![StatementType::Value(Value { value: Box::new(FrontMatterType::VARIABLE("m.name".to_string())) })],
+        };
+
+        let rows = query.execute(&FixtureSource);
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].get("m.name"), Some(&FrontMatterType::STRING("run".to_string())));
+        assert_eq!(rows[1].get("m.name"), Some(&FrontMatterType::STRING("helper".to_string())));
+    }
+
+    #[test]
+    fn expected_type_of_an_empty_array_is_unknown_not_untypeable() {
+        let env = HashMap::new();
+        let empty = FrontMatterType::ARRAY(Vec::new());
+        assert_eq!(empty.expected_type(&env), Some(FrontMatterValueType::Array(Box::new(FrontMatterValueType::Unknown))));
+    }
+
+    #[test]
+    fn expected_type_of_a_homogeneous_array_is_its_element_type() {
+        let env = HashMap::new();
+        let numbers = FrontMatterType::ARRAY(vec![FrontMatterType::NUMBER(1), FrontMatterType::NUMBER(2)]);
+        assert_eq!(numbers.expected_type(&env), Some(FrontMatterValueType::Array(Box::new(FrontMatterValueType::Number))));
+    }
+
+    #[test]
+    fn expected_type_of_a_mixed_element_array_is_none() {
+        let env = HashMap::new();
+        let mixed = FrontMatterType::ARRAY(vec![FrontMatterType::NUMBER(1), FrontMatterType::STRING("x".to_string())]);
+        assert_eq!(mixed.expected_type(&env), None);
+    }
+
+    #[test]
+    fn expected_type_of_a_query_statement_is_an_array_of_objects() {
+        // `execute()` returns one `HashMap<String, FrontMatterType>` row per match, so the
+        // inferred type must be `Array(Object)` regardless of what `select` projects.
+        let query = FrontMatterType::QUERY_STATEMENT(ShirePsiQueryStatement {
+            from: vec![VariableElement { name: "m".to_string(), kind: "Method".to_string() }],
+            where_clause: Box::new(equality("m.name", "m.name")),
+            select: vec![StatementType::Value(Value { value: Box::new(FrontMatterType::VARIABLE("m.name".to_string())) })],
+        });
+        assert_eq!(query.expected_type(&HashMap::new()), Some(FrontMatterValueType::Array(Box::new(FrontMatterValueType::Object))));
+    }
+
+    #[test]
+    fn to_json_renders_scalars_and_arrays() {
+        assert_eq!(FrontMatterType::STRING("hi".to_string()).to_json(), "\"hi\"");
+        assert_eq!(FrontMatterType::NUMBER(3).to_json(), "3");
+        assert_eq!(FrontMatterType::BOOLEAN(true).to_json(), "true");
+        assert_eq!(FrontMatterType::EMPTY.to_json(), "null");
+        let array = FrontMatterType::ARRAY(vec![FrontMatterType::NUMBER(1), FrontMatterType::NUMBER(2)]);
+        assert_eq!(array.to_json(), "[1,2]");
+    }
+
+    #[test]
+    fn to_json_sorts_object_fields_for_deterministic_output() {
+        let object = FrontMatterType::OBJECT(HashMap::from([
+            ("b".to_string(), FrontMatterType::NUMBER(2)),
+            ("a".to_string(), FrontMatterType::NUMBER(1)),
+        ]));
+        assert_eq!(object.to_json(), "{\"a\":1,\"b\":2}");
+    }
+
+    #[test]
+    fn to_json_tags_kinds_with_no_json_equivalent() {
+        let pattern = FrontMatterType::PATTERN(RuleBasedPatternAction { pattern: "*.java".to_string(), processors: Vec::new() });
+        assert_eq!(pattern.to_json(), "{\"type\":\"pattern\",\"source\":\"*.java -> \"}");
+    }
+
+    #[test]
+    fn to_json_escapes_control_characters_beyond_newline() {
+        let value = FrontMatterType::STRING("a\tb\rc\u{1}d".to_string());
+        assert_eq!(value.to_json(), "\"a\\tb\\rc\\u0001d\"");
+    }
+
+    #[test]
+    fn to_yaml_renders_an_array_as_a_dash_list() {
+        let array = FrontMatterType::ARRAY(vec![FrontMatterType::STRING("a".to_string()), FrontMatterType::STRING("b".to_string())]);
+        assert_eq!(array.to_yaml(), "\n- \"a\"\n- \"b\"");
+    }
+
+    #[test]
+    fn to_yaml_renders_empty_collections_as_flow_style() {
+        assert_eq!(FrontMatterType::ARRAY(Vec::new()).to_yaml(), "[]");
+        assert_eq!(FrontMatterType::OBJECT(HashMap::new()).to_yaml(), "{}");
+    }
 }
\ No newline at end of file
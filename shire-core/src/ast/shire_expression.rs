@@ -1,7 +1,8 @@
+use crate::ast::env::{Env, Value as EnvValue};
+use crate::ast::eval_error::EvalError;
 use crate::ast::front_matter_type::FrontMatterType;
-use crate::ast::pattern_action_fun::PatternActionFunc;
+use crate::ast::typed_statement::ValueType;
 use std::any::Any;
-use std::collections::HashMap;
 use std::fmt::Debug;
 
 #[derive(Debug, Eq, PartialEq, Clone)]
@@ -14,31 +15,52 @@ pub enum StatementType {
     NotExpression(NotExpression),
     MethodCall(MethodCall),
     Value(Value),
-    Processor(Processor),
     CaseKeyValue(CaseKeyValue),
     ConditionCase(ConditionCase),
 }
 
 pub trait Statement {
-    fn evaluate(&self, variables: &HashMap<String, String>) -> Result<Box<dyn std::any::Any>, String>;
+    fn evaluate(&self, variables: &Env) -> Result<Box<dyn std::any::Any>, EvalError>;
     fn display(&self) -> String;
+
+    /// Simplifies this node against whatever `variables` are already known, returning a
+    /// reduced `StatementType`. Sub-trees that still reference unknown variables are
+    /// returned unchanged so the rest of the tree can be folded around them.
+    fn partial_eval(&self, variables: &Env) -> StatementType;
+}
+
+/// Returns the boolean a `StatementType` folded down to, if it is already a literal.
+fn as_bool_literal(statement: &StatementType) -> Option<bool> {
+    match statement {
+        StatementType::Value(Value { value }) => match value.as_ref() {
+            FrontMatterType::BOOLEAN(b) => Some(*b),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn bool_value(b: bool) -> StatementType {
+    StatementType::Value(Value { value: Box::new(FrontMatterType::BOOLEAN(b)) })
 }
 
 impl Statement for StatementType {
     // evaluate 函数
-    fn evaluate(&self, variables: &HashMap<String, String>) -> Result<Box<dyn std::any::Any>, String> {
+    fn evaluate(&self, variables: &Env) -> Result<Box<dyn std::any::Any>, EvalError> {
+        // Each arm already returns `Result<Box<dyn Any>, EvalError>`, so it must be propagated
+        // directly here rather than boxed again — boxing the `Result` itself would make every
+        // downstream `downcast_ref::<T>()` fail, since the boxed value is a `Result`, not a `T`.
         match &self {
             StatementType::Operator(op) => Ok(Box::new(op.type_.display().clone())),
             StatementType::StringOperator(op) => Ok(Box::new(op.type_.display().clone())),
-            StatementType::Comparison(comp) => Ok(Box::new(comp.evaluate(variables))),
-            StatementType::StringComparison(comp) => Ok(Box::new(comp.evaluate(variables))),
-            StatementType::LogicalExpression(expr) => Ok(Box::new(expr.evaluate(variables))),
-            StatementType::NotExpression(expr) => Ok(Box::new(expr.evaluate(variables))),
-            StatementType::MethodCall(call) => Ok(Box::new(call.evaluate(variables))),
-            StatementType::Value(val) => Ok(Box::new(val.evaluate(variables))),
-            StatementType::Processor(proc) => Ok(Box::new(proc.evaluate(variables))),
-            StatementType::CaseKeyValue(case) => Ok(Box::new(case.evaluate(variables))),
-            StatementType::ConditionCase(cond) => Ok(Box::new(cond.evaluate(variables))),
+            StatementType::Comparison(comp) => comp.evaluate(variables),
+            StatementType::StringComparison(comp) => comp.evaluate(variables),
+            StatementType::LogicalExpression(expr) => expr.evaluate(variables),
+            StatementType::NotExpression(expr) => expr.evaluate(variables),
+            StatementType::MethodCall(call) => call.evaluate(variables),
+            StatementType::Value(val) => val.evaluate(variables),
+            StatementType::CaseKeyValue(case) => case.evaluate(variables),
+            StatementType::ConditionCase(cond) => cond.evaluate(variables),
         }
     }
 
@@ -107,25 +129,50 @@ impl Statement for StatementType {
                 )
             }
             StatementType::Value(val) => val.value.display(),
-            StatementType::Processor(proc) => proc.processors.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(" | "),
             _ => "Unsupported statement type".to_string(),
         }
     }
+
+    fn partial_eval(&self, variables: &Env) -> StatementType {
+        match self {
+            StatementType::Operator(op) => op.partial_eval(variables),
+            StatementType::StringOperator(op) => op.partial_eval(variables),
+            StatementType::Comparison(comp) => comp.partial_eval(variables),
+            StatementType::StringComparison(comp) => comp.partial_eval(variables),
+            StatementType::LogicalExpression(expr) => expr.partial_eval(variables),
+            StatementType::NotExpression(expr) => expr.partial_eval(variables),
+            StatementType::MethodCall(call) => call.partial_eval(variables),
+            StatementType::Value(val) => val.partial_eval(variables),
+            StatementType::CaseKeyValue(case) => case.partial_eval(variables),
+            StatementType::ConditionCase(cond) => cond.partial_eval(variables),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Value {
-    value: Box<FrontMatterType>,
+    pub(crate) value: Box<FrontMatterType>,
 }
 
 impl Statement for Value {
-    fn evaluate(&self, _variables: &HashMap<String, String>) -> Result<Box<dyn std::any::Any>, String> {
+    fn evaluate(&self, variables: &Env) -> Result<Box<dyn std::any::Any>, EvalError> {
         let result: Box<dyn std::any::Any> = match &self.value.as_ref() {
             FrontMatterType::STRING(val) => Box::new(val.clone()),
             FrontMatterType::NUMBER(val) => Box::new(*val),
             FrontMatterType::DATE(val) => Box::new(val.clone()),
             FrontMatterType::BOOLEAN(val) => Box::new(*val),
-            _ => return Err(format!("Unsupported value type: {:?}", self.value)),
+            FrontMatterType::VARIABLE(var) => {
+                // Resolved straight from the typed `Env`, so a boolean variable reaches
+                // `LogicalExpression`/`NotExpression` as a native `bool`, not a string.
+                match variables.get(var).ok_or_else(|| EvalError::UndefinedVariable(var.clone()))? {
+                    EnvValue::Str(s) => Box::new(s.clone()),
+                    EnvValue::Number(n) => Box::new(*n),
+                    EnvValue::Boolean(b) => Box::new(*b),
+                    EnvValue::Date(d) => Box::new(d.clone()),
+                    EnvValue::List(items) => Box::new(items.clone()),
+                }
+            }
+            _ => return Err(EvalError::UnsupportedOperand(format!("{:?}", self.value))),
         };
         Ok(result)
     }
@@ -133,6 +180,10 @@ impl Statement for Value {
     fn display(&self) -> String {
         self.value.display()
     }
+
+    fn partial_eval(&self, _variables: &Env) -> StatementType {
+        StatementType::Value(self.clone())
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -166,7 +217,7 @@ impl OperatorType {
 }
 
 impl Statement for OperatorType {
-    fn evaluate(&self, variables: &HashMap<String, String>) -> Result<Box<dyn Any>, String> {
+    fn evaluate(&self, variables: &Env) -> Result<Box<dyn Any>, EvalError> {
         Ok(Box::new(self.display().to_string()))
     }
 
@@ -184,6 +235,9 @@ impl Statement for OperatorType {
         }
     }
 
+    fn partial_eval(&self, _variables: &Env) -> StatementType {
+        StatementType::Operator(Operator { type_: self.clone() })
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -195,7 +249,7 @@ pub enum StringOperator {
 }
 
 impl Statement for StringOperator {
-    fn evaluate(&self, variables: &HashMap<String, String>) -> Result<Box<dyn Any>, String> {
+    fn evaluate(&self, variables: &Env) -> Result<Box<dyn Any>, EvalError> {
         Ok(Box::new(self.display().to_string()))
     }
 
@@ -207,66 +261,175 @@ impl Statement for StringOperator {
             StringOperator::Matches => format!("{}", "matches"),
         }
     }
+
+    fn partial_eval(&self, _variables: &Env) -> StatementType {
+        StatementType::StringOperator(StringOperatorStatement { type_: self.clone() })
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Operator {
-    type_: OperatorType,
+    pub(crate) type_: OperatorType,
 }
 
 impl Statement for Operator {
-    fn evaluate(&self, _variables: &HashMap<String, String>) -> Result<Box<dyn std::any::Any>, String> {
+    fn evaluate(&self, _variables: &Env) -> Result<Box<dyn std::any::Any>, EvalError> {
         Ok(Box::new(self.type_.display().to_string()))
     }
 
     fn display(&self) -> String {
         self.type_.display()
     }
+
+    fn partial_eval(&self, _variables: &Env) -> StatementType {
+        StatementType::Operator(self.clone())
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct StringOperatorStatement {
-    type_: StringOperator,
+    pub(crate) type_: StringOperator,
 }
 
 impl Statement for StringOperatorStatement {
-    fn evaluate(&self, _variables: &HashMap<String, String>) -> Result<Box<dyn std::any::Any>, String> {
+    fn evaluate(&self, _variables: &Env) -> Result<Box<dyn std::any::Any>, EvalError> {
         Ok(Box::new(self.type_.display().to_string()))
     }
 
     fn display(&self) -> String {
         self.type_.display()
     }
+
+    fn partial_eval(&self, _variables: &Env) -> StatementType {
+        StatementType::StringOperator(self.clone())
+    }
+}
+
+/// A comparison operand resolved to a concrete, orderable Rust type, so `<`/`>` compare the
+/// right representation instead of always falling back to lexical string ordering.
+#[derive(Debug, Clone, PartialEq)]
+enum OrderedOperand {
+    Number(i32),
+    Date((i64, u32, u32)),
+    Str(String),
+}
+
+impl OrderedOperand {
+    fn value_type(&self) -> ValueType {
+        match self {
+            OrderedOperand::Number(_) => ValueType::Number,
+            OrderedOperand::Date(_) => ValueType::Date,
+            OrderedOperand::Str(_) => ValueType::Str,
+        }
+    }
+}
+
+/// Parses a `YYYY-MM-DD` front-matter date into a tuple that orders chronologically.
+fn parse_date(value: &str) -> Option<(i64, u32, u32)> {
+    let mut parts = value.splitn(3, '-');
+    let year = parts.next()?.parse::<i64>().ok()?;
+    let month = parts.next()?.parse::<u32>().ok()?;
+    let day = parts.next()?.parse::<u32>().ok()?;
+    Some((year, month, day))
+}
+
+/// Classifies a literal `FrontMatterType` operand, without resorting to an environment lookup.
+fn classify_literal(value: &FrontMatterType) -> Option<OrderedOperand> {
+    match value {
+        FrontMatterType::NUMBER(n) => Some(OrderedOperand::Number(*n)),
+        FrontMatterType::DATE(d) => Some(OrderedOperand::Date(parse_date(d)?)),
+        FrontMatterType::STRING(s) => Some(OrderedOperand::Str(s.clone())),
+        _ => None,
+    }
+}
+
+/// Resolves a `VARIABLE` from the environment, coercing the raw string toward `hint`'s type
+/// (the other operand's type) before falling back to a plain string.
+fn resolve_variable_operand(raw: &str, hint: Option<&OrderedOperand>) -> OrderedOperand {
+    match hint {
+        Some(OrderedOperand::Number(_)) => raw
+            .parse::<i32>()
+            .map(OrderedOperand::Number)
+            .unwrap_or_else(|_| OrderedOperand::Str(raw.to_string())),
+        Some(OrderedOperand::Date(_)) => parse_date(raw)
+            .map(OrderedOperand::Date)
+            .unwrap_or_else(|| OrderedOperand::Str(raw.to_string())),
+        Some(OrderedOperand::Str(_)) => OrderedOperand::Str(raw.to_string()),
+        _ => {
+            if let Ok(n) = raw.parse::<i32>() {
+                OrderedOperand::Number(n)
+            } else if let Some(date) = parse_date(raw) {
+                OrderedOperand::Date(date)
+            } else {
+                OrderedOperand::Str(raw.to_string())
+            }
+        }
+    }
+}
+
+fn compare_ordered<T: PartialOrd>(operator: &OperatorType, left: &T, right: &T) -> Result<bool, EvalError> {
+    match operator {
+        OperatorType::Equal => Ok(left == right),
+        OperatorType::NotEqual => Ok(left != right),
+        OperatorType::LessThan => Ok(left < right),
+        OperatorType::GreaterThan => Ok(left > right),
+        OperatorType::LessEqual => Ok(left <= right),
+        OperatorType::GreaterEqual => Ok(left >= right),
+        _ => Err(EvalError::UnsupportedOperand(format!("{:?} is not a comparison operator", operator))),
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Comparison {
-    left: Box<FrontMatterType>,
-    operator: Operator,
-    right: Box<FrontMatterType>,
+    pub(crate) left: Box<FrontMatterType>,
+    pub(crate) operator: Operator,
+    pub(crate) right: Box<FrontMatterType>,
 }
 
-impl Statement for Comparison {
-    fn evaluate(&self, variables: &HashMap<String, String>) -> Result<Box<dyn std::any::Any>, String> {
-        let left_value = match &self.left.as_ref() {
-            FrontMatterType::STRING(val) => val.clone(),
-            FrontMatterType::VARIABLE(var) => variables.get(var).cloned().unwrap_or_else(|| "".to_string()),
-            _ => return Err("Unsupported left value type".to_string()),
-        };
-
-        let right_value = match &self.right.as_ref() {
-            FrontMatterType::STRING(val) => val.clone(),
-            _ => return Err("Unsupported right value type".to_string()),
-        };
+impl Comparison {
+    fn resolve(
+        &self,
+        operand: &FrontMatterType,
+        hint: Option<&OrderedOperand>,
+        variables: &Env,
+    ) -> Result<OrderedOperand, EvalError> {
+        match operand {
+            FrontMatterType::VARIABLE(var) => match variables
+                .get(var)
+                .ok_or_else(|| EvalError::UndefinedVariable(var.clone()))?
+            {
+                EnvValue::Number(n) => Ok(OrderedOperand::Number(*n)),
+                EnvValue::Date(d) => parse_date(d)
+                    .map(OrderedOperand::Date)
+                    .ok_or_else(|| EvalError::UnsupportedOperand(format!("invalid date: {}", d))),
+                EnvValue::Str(s) => Ok(resolve_variable_operand(s, hint)),
+                other => Err(EvalError::UnsupportedOperand(format!("{:?} is not orderable", other))),
+            },
+            other => classify_literal(other).ok_or_else(|| EvalError::UnsupportedOperand(format!("{:?}", other))),
+        }
+    }
+}
 
-        let result = match self.operator.type_ {
-            OperatorType::Equal => left_value == right_value,
-            OperatorType::NotEqual => left_value != right_value,
-            OperatorType::LessThan => left_value < right_value,
-            OperatorType::GreaterThan => left_value > right_value,
-            OperatorType::LessEqual => left_value <= right_value,
-            OperatorType::GreaterEqual => left_value >= right_value,
-            _ => return Err("Invalid comparison operator".to_string()),
+impl Statement for Comparison {
+    fn evaluate(&self, variables: &Env) -> Result<Box<dyn std::any::Any>, EvalError> {
+        // Resolve whichever side is a plain literal first, so it can hint the type a
+        // `VARIABLE` on the other side should be coerced toward.
+        let left_literal = classify_literal(&self.left);
+        let right_literal = classify_literal(&self.right);
+
+        let left_value = self.resolve(&self.left, right_literal.as_ref(), variables)?;
+        let right_value = self.resolve(&self.right, left_literal.as_ref(), variables)?;
+
+        let result = match (&left_value, &right_value) {
+            (OrderedOperand::Number(l), OrderedOperand::Number(r)) => compare_ordered(&self.operator.type_, l, r)?,
+            (OrderedOperand::Date(l), OrderedOperand::Date(r)) => compare_ordered(&self.operator.type_, l, r)?,
+            (OrderedOperand::Str(l), OrderedOperand::Str(r)) => compare_ordered(&self.operator.type_, l, r)?,
+            _ => {
+                return Err(EvalError::TypeMismatch {
+                    expected: left_value.value_type(),
+                    actual: format!("{:?}", right_value.value_type()),
+                })
+            }
         };
 
         Ok(Box::new(result))
@@ -275,17 +438,27 @@ impl Statement for Comparison {
     fn display(&self) -> String {
         format!("{} {} {}", self.left.display(), self.operator.display(), self.right.display())
     }
+
+    fn partial_eval(&self, variables: &Env) -> StatementType {
+        match self.evaluate(variables) {
+            Ok(result) => match result.downcast_ref::<bool>() {
+                Some(value) => bool_value(*value),
+                None => StatementType::Comparison(self.clone()),
+            },
+            Err(_) => StatementType::Comparison(self.clone()),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct StringComparison {
-    variable: String,
-    operator: StringOperatorStatement,
-    value: String,
+    pub(crate) variable: String,
+    pub(crate) operator: StringOperatorStatement,
+    pub(crate) value: String,
 }
 
 impl Statement for StringComparison {
-    fn evaluate(&self, _variables: &HashMap<String, String>) -> Result<Box<dyn std::any::Any>, String> {
+    fn evaluate(&self, _variables: &Env) -> Result<Box<dyn std::any::Any>, EvalError> {
         let result = match self.operator.type_ {
             StringOperator::Contains => self.variable.contains(&self.value),
             StringOperator::StartsWith => self.variable.starts_with(&self.value),
@@ -293,7 +466,12 @@ impl Statement for StringComparison {
             StringOperator::Matches => {
                 match regex::Regex::new(&self.value) {
                     Ok(regex) => regex.is_match(&self.variable),
-                    Err(_) => return Err("Invalid regex pattern".to_string()),
+                    Err(err) => {
+                        return Err(EvalError::InvalidRegex {
+                            pattern: self.value.clone(),
+                            source: err.to_string(),
+                        })
+                    }
                 }
             }
         };
@@ -304,17 +482,29 @@ impl Statement for StringComparison {
     fn display(&self) -> String {
         format!("{} {} {}", self.variable, self.operator.display(), self.value)
     }
+
+    fn partial_eval(&self, _variables: &Env) -> StatementType {
+        // `variable`/`value` are already literal text, so this folds unless a `matches`
+        // pattern fails to compile, in which case the node is returned unchanged.
+        match self.evaluate(_variables) {
+            Ok(result) => match result.downcast_ref::<bool>() {
+                Some(value) => bool_value(*value),
+                None => StatementType::StringComparison(self.clone()),
+            },
+            Err(_) => StatementType::StringComparison(self.clone()),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct LogicalExpression {
-    left: Box<StatementType>,
-    operator: OperatorType,
-    right: Box<StatementType>,
+    pub(crate) left: Box<StatementType>,
+    pub(crate) operator: OperatorType,
+    pub(crate) right: Box<StatementType>,
 }
 
 impl Statement for LogicalExpression {
-    fn evaluate(&self, variables: &HashMap<String, String>) -> Result<Box<dyn std::any::Any>, String> {
+    fn evaluate(&self, variables: &Env) -> Result<Box<dyn std::any::Any>, EvalError> {
         // Evaluate the left and right operands
         let left_result = self.left.as_ref().evaluate(variables);
         let right_result = self.right.as_ref().evaluate(variables);
@@ -323,21 +513,21 @@ impl Statement for LogicalExpression {
         let left = left_result?;
         let left_value = match left.downcast_ref::<bool>() {
             Some(value) => value,
-            None => return Err("Left operand is not of type bool".to_string()),
+            None => return Err(EvalError::TypeMismatch { expected: ValueType::Bool, actual: "left operand".to_string() }),
         };
 
 
         let right = right_result?;
         let right_value = match right.downcast_ref::<bool>() {
             Some(value) => value,
-            None => return Err("Right operand is not of type bool".to_string()),
+            None => return Err(EvalError::TypeMismatch { expected: ValueType::Bool, actual: "right operand".to_string() }),
         };
 
         // Compute the result based on the operator
         let result = match self.operator {
             OperatorType::And => *left_value && *right_value,
             OperatorType::Or => *left_value || *right_value,
-            _ => return Err("Invalid logical operator".to_string()),
+            _ => return Err(EvalError::UnsupportedOperand(format!("{:?} is not a logical operator", self.operator))),
         };
 
         // Return the result as a Box<dyn Any> wrapped in Ok
@@ -347,15 +537,48 @@ impl Statement for LogicalExpression {
     fn display(&self) -> String {
         format!("{} {} {}", self.left.as_ref().display(), self.operator.display(), self.right.as_ref().display())
     }
+
+    fn partial_eval(&self, variables: &Env) -> StatementType {
+        let left = self.left.as_ref().partial_eval(variables);
+        let right = self.right.as_ref().partial_eval(variables);
+
+        match self.operator {
+            OperatorType::And => match (as_bool_literal(&left), as_bool_literal(&right)) {
+                (Some(false), _) | (_, Some(false)) => bool_value(false),
+                (Some(true), _) => right,
+                (_, Some(true)) => left,
+                _ => StatementType::LogicalExpression(LogicalExpression {
+                    left: Box::new(left),
+                    operator: self.operator.clone(),
+                    right: Box::new(right),
+                }),
+            },
+            OperatorType::Or => match (as_bool_literal(&left), as_bool_literal(&right)) {
+                (Some(true), _) | (_, Some(true)) => bool_value(true),
+                (Some(false), _) => right,
+                (_, Some(false)) => left,
+                _ => StatementType::LogicalExpression(LogicalExpression {
+                    left: Box::new(left),
+                    operator: self.operator.clone(),
+                    right: Box::new(right),
+                }),
+            },
+            _ => StatementType::LogicalExpression(LogicalExpression {
+                left: Box::new(left),
+                operator: self.operator.clone(),
+                right: Box::new(right),
+            }),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct NotExpression {
-    operand: Box<StatementType>,
+    pub(crate) operand: Box<StatementType>,
 }
 
 impl Statement for NotExpression {
-    fn evaluate(&self, variables: &HashMap<String, String>) -> Result<Box<dyn std::any::Any>, String> {
+    fn evaluate(&self, variables: &Env) -> Result<Box<dyn std::any::Any>, EvalError> {
         // Evaluate the operand and get the result as a Box<dyn Any>
         let operand_result = self.operand.as_ref().evaluate(variables);
 
@@ -363,7 +586,7 @@ impl Statement for NotExpression {
         let op = operand_result?;
         let operand_value = match op.downcast_ref::<bool>() {
             Some(value) => value,
-            None => return Err("Operand is not of type bool".to_string()),
+            None => return Err(EvalError::TypeMismatch { expected: ValueType::Bool, actual: "operand".to_string() }),
         };
 
         // Compute the negation of the boolean value
@@ -376,13 +599,21 @@ impl Statement for NotExpression {
     fn display(&self) -> String {
         format!("!{}", self.operand.as_ref().display())
     }
+
+    fn partial_eval(&self, variables: &Env) -> StatementType {
+        let operand = self.operand.as_ref().partial_eval(variables);
+        match as_bool_literal(&operand) {
+            Some(value) => bool_value(!value),
+            None => StatementType::NotExpression(NotExpression { operand: Box::new(operand) }),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct MethodCall {
-    object_name: Box<FrontMatterType>,
-    method_name: Box<FrontMatterType>,
-    arguments: Option<Vec<FrontMatterType>>,
+    pub(crate) object_name: Box<FrontMatterType>,
+    pub(crate) method_name: Box<FrontMatterType>,
+    pub(crate) arguments: Option<Vec<FrontMatterType>>,
 }
 
 impl MethodCall {
@@ -401,69 +632,69 @@ impl MethodCall {
         &self,
         method_name: &str,
         parameters: Option<Vec<String>>,
-        value: &str,
-    ) -> Box<dyn std::any::Any> {
-        match method_name {
-            "length" => Box::new(value.len()),
-            "trim" => Box::new(value.trim().to_string()),
-            "contains" => {
-                // let param = parameters.unwrap().get(0).unwrap();
-                let params = parameters.unwrap();
-                let param = params.get(0).unwrap(); // This is now a longer-lived value
+        subject: &EnvValue,
+    ) -> Result<Box<dyn std::any::Any>, EvalError> {
+        let first_param = |params: Option<Vec<String>>| -> Result<String, EvalError> {
+            params
+                .and_then(|params| params.into_iter().next())
+                .ok_or_else(|| EvalError::UnsupportedOperand(format!("{} requires an argument", method_name)))
+        };
 
-                Box::new(value.contains(param))
-            }
-            "startsWith" => {
-                // let param = parameters.unwrap().get(0).unwrap();
-                let params = parameters.unwrap();
-                let param = params.get(0).unwrap(); // This is now a longer-lived value
+        // `length`/`isEmpty`/`isNotEmpty` work the same way over a list subject as a string one;
+        // everything else requires an actual string to operate on.
+        match method_name {
+            "length" => return Ok(Box::new(subject.len())),
+            "isEmpty" => return Ok(Box::new(subject.is_empty())),
+            "isNotEmpty" => return Ok(Box::new(!subject.is_empty())),
+            _ => {}
+        }
 
-                Box::new(value.starts_with(param))
-            }
-            "endsWith" => {
-                // let param = parameters.unwrap().get(0).unwrap();
-                let params = parameters.unwrap();
-                let param = params.get(0).unwrap(); // This is now a longer-lived value
+        let value = subject
+            .as_str()
+            .ok_or_else(|| EvalError::UnsupportedMethod(format!("{} on a non-string value", method_name)))?;
 
-                Box::new(value.ends_with(param))
-            }
+        let result: Box<dyn std::any::Any> = match method_name {
+            "trim" => Box::new(value.trim().to_string()),
+            "contains" => Box::new(value.contains(&first_param(parameters)?)),
+            "startsWith" => Box::new(value.starts_with(&first_param(parameters)?)),
+            "endsWith" => Box::new(value.ends_with(&first_param(parameters)?)),
             "lowercase" => Box::new(value.to_lowercase()),
             "uppercase" => Box::new(value.to_uppercase()),
-            "isEmpty" => Box::new(value.is_empty()),
-            "isNotEmpty" => Box::new(!value.is_empty()),
-            "first" => Box::new(value.chars().next().unwrap().to_string()),
-            "last" => Box::new(value.chars().last().unwrap().to_string()),
+            "first" => Box::new(value.chars().next().ok_or(EvalError::EmptyStringAccess)?.to_string()),
+            "last" => Box::new(value.chars().last().ok_or(EvalError::EmptyStringAccess)?.to_string()),
             "matches" => {
-                // let param = parameters.unwrap().get(0).unwrap();
-                let params = parameters.unwrap();
-                let param = params.get(0).unwrap(); // This is now a longer-lived value
-
-                let regex = regex::Regex::new(param).unwrap();
+                let pattern = first_param(parameters)?;
+                let regex = regex::Regex::new(&pattern).map_err(|err| EvalError::InvalidRegex {
+                    pattern: pattern.clone(),
+                    source: err.to_string(),
+                })?;
                 Box::new(regex.is_match(value))
             }
-            _ => panic!("Unsupported method: {}", method_name),
-        }
+            other => return Err(EvalError::UnsupportedMethod(other.to_string())),
+        };
+
+        Ok(result)
     }
 }
 
 impl Statement for MethodCall {
-    fn evaluate(&self, variables: &HashMap<String, String>) -> Result<Box<dyn std::any::Any>, String> {
-        // Resolve the object name to a string value
-        let value = match &self.object_name.as_ref() {
-            FrontMatterType::STRING(s) => s.clone(),
-            FrontMatterType::VARIABLE(var) => variables.get(var).cloned().unwrap_or_else(|| "".to_string()),
-            _ => return Err("Unsupported object name type".to_string()),
+    fn evaluate(&self, variables: &Env) -> Result<Box<dyn std::any::Any>, EvalError> {
+        // Resolve the object name to a typed value, preserving lists/numbers instead of
+        // flattening everything to a string up front.
+        let subject = match &self.object_name.as_ref() {
+            FrontMatterType::STRING(s) => EnvValue::Str(s.clone()),
+            FrontMatterType::VARIABLE(var) => variables
+                .get(var)
+                .cloned()
+                .ok_or_else(|| EvalError::UndefinedVariable(var.clone()))?,
+            _ => return Err(EvalError::UnsupportedOperand(format!("{:?}", self.object_name))),
         };
 
         // Prepare method name and parameters
         let method_name = self.method_name.display();
         let parameters = self.parameters();
 
-        // Evaluate the expression and handle potential errors
-        // self.evaluate_expression(&method_name, parameters, &value)
-        //     .map(|result| Box::new(result) as Box<dyn std::any::Any>)
-        //     .map_err(|e| e.to_string())
-        Ok(Box::new(self.evaluate_expression(&method_name, parameters, &value)))
+        self.evaluate_expression(&method_name, parameters, &subject)
     }
 
     fn display(&self) -> String {
@@ -473,35 +704,21 @@ impl Statement for MethodCall {
 
         format!("{}{}{}", self.object_name.display(), self.method_name.display(), parameters)
     }
-}
 
-#[derive(Debug, Clone, Eq, PartialEq)]
-pub struct Processor {
-    processors: Vec<PatternActionFunc>,
-}
-
-impl Statement for Processor {
-    fn evaluate(&self, _variables: &HashMap<String, String>) -> Result<Box<dyn std::any::Any>, String> {
-        let processors = self.processors.clone();
-
-        // Convert Vec<PatternActionFunc> to Box<dyn std::any::Any>
-        Ok(Box::new(processors) as Box<dyn std::any::Any>)
-    }
-
-    fn display(&self) -> String {
-        self.processors.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(" | ")
+    fn partial_eval(&self, _variables: &Env) -> StatementType {
+        StatementType::MethodCall(self.clone())
     }
 }
 
 // CaseKeyValue 结构体
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct CaseKeyValue {
-    key: Box<FrontMatterType>,
-    value: Box<FrontMatterType>,
+    pub(crate) key: Box<FrontMatterType>,
+    pub(crate) value: Box<FrontMatterType>,
 }
 
 impl Statement for CaseKeyValue {
-    fn evaluate(&self, _variables: &HashMap<String, String>) -> Result<Box<dyn std::any::Any>, String> {
+    fn evaluate(&self, _variables: &Env) -> Result<Box<dyn std::any::Any>, EvalError> {
         // Create the tuple from the key and value
         let result = (
             self.key.display(),
@@ -515,16 +732,20 @@ impl Statement for CaseKeyValue {
     fn display(&self) -> String {
         format!("\"{}\" -> {}", self.key.display(), self.value.display())
     }
+
+    fn partial_eval(&self, _variables: &Env) -> StatementType {
+        StatementType::CaseKeyValue(self.clone())
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct ConditionCase {
-    conditions: Vec<FrontMatterType>,
-    cases: Vec<FrontMatterType>,
+    pub(crate) conditions: Vec<FrontMatterType>,
+    pub(crate) cases: Vec<FrontMatterType>,
 }
 
 impl Statement for ConditionCase {
-    fn evaluate(&self, _variables: &HashMap<String, String>) -> Result<Box<dyn std::any::Any>, String> {
+    fn evaluate(&self, _variables: &Env) -> Result<Box<dyn std::any::Any>, EvalError> {
         // Create vectors of strings from the conditions and cases
         let condition: Vec<String> = self.conditions.iter().map(|cond| cond.display()).collect();
         let case: Vec<String> = self.cases.iter().map(|case| case.display()).collect();
@@ -542,4 +763,154 @@ impl Statement for ConditionCase {
 
         format!("case \"{}\" -> {}", conditions, cases)
     }
+
+    fn partial_eval(&self, _variables: &Env) -> StatementType {
+        StatementType::ConditionCase(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comparison(left: FrontMatterType, operator: OperatorType, right: FrontMatterType) -> Comparison {
+        Comparison { left: Box::new(left), operator: Operator { type_: operator }, right: Box::new(right) }
+    }
+
+    fn eval_bool(comp: &Comparison, variables: &Env) -> bool {
+        *comp.evaluate(variables).unwrap().downcast_ref::<bool>().unwrap()
+    }
+
+    #[test]
+    fn compares_numbers_numerically_not_lexically() {
+        // Lexically "9" > "10", but numerically 9 < 10 — this is what type-aware comparison buys.
+        let comp = comparison(FrontMatterType::NUMBER(9), OperatorType::LessThan, FrontMatterType::NUMBER(10));
+        assert!(eval_bool(&comp, &Env::new()));
+    }
+
+    #[test]
+    fn compares_numeric_variable_against_a_numeric_literal() {
+        let mut env = Env::new();
+        env.insert("count", EnvValue::Number(3));
+        let comp = comparison(FrontMatterType::VARIABLE("count".to_string()), OperatorType::GreaterThan, FrontMatterType::NUMBER(2));
+        assert!(eval_bool(&comp, &env));
+    }
+
+    #[test]
+    fn compares_dates_chronologically_not_lexically() {
+        // Lexically "2024-9-01" > "2024-10-01", but chronologically September comes first.
+        let comp = comparison(
+            FrontMatterType::DATE("2024-9-01".to_string()),
+            OperatorType::LessThan,
+            FrontMatterType::DATE("2024-10-01".to_string()),
+        );
+        assert!(eval_bool(&comp, &Env::new()));
+    }
+
+    #[test]
+    fn date_variable_is_coerced_toward_the_literal_operands_type() {
+        let mut env = Env::new();
+        env.insert("released", EnvValue::Str("2023-01-01".to_string()));
+        let comp = comparison(
+            FrontMatterType::VARIABLE("released".to_string()),
+            OperatorType::LessThan,
+            FrontMatterType::DATE("2023-06-01".to_string()),
+        );
+        assert!(eval_bool(&comp, &env));
+    }
+
+    #[test]
+    fn string_hinted_variable_is_not_misparsed_as_a_number_or_date() {
+        // "2023" looks numeric, so without a `Str` hint arm this variable would be coerced to a
+        // Number (and then mismatch the string literal) instead of compared as text.
+        let mut env = Env::new();
+        env.insert("version", EnvValue::Str("2023".to_string()));
+        let comp = comparison(
+            FrontMatterType::VARIABLE("version".to_string()),
+            OperatorType::Equal,
+            FrontMatterType::STRING("2023".to_string()),
+        );
+        assert!(eval_bool(&comp, &env));
+    }
+
+    #[test]
+    fn mismatched_operand_kinds_are_a_type_error() {
+        let comp = comparison(FrontMatterType::NUMBER(1), OperatorType::Equal, FrontMatterType::STRING("1".to_string()));
+        assert!(matches!(comp.evaluate(&Env::new()), Err(EvalError::TypeMismatch { .. })));
+    }
+
+    #[test]
+    fn partial_eval_folds_a_literal_comparison_to_a_bool_value() {
+        let comp = comparison(FrontMatterType::NUMBER(1), OperatorType::LessThan, FrontMatterType::NUMBER(2));
+        let folded = StatementType::Comparison(comp).partial_eval(&Env::new());
+        assert_eq!(folded, bool_value(true));
+    }
+
+    #[test]
+    fn partial_eval_short_circuits_and_over_a_false_literal() {
+        // `false && <anything still referencing an unknown variable>` folds to `false` without
+        // needing to resolve the right-hand side at all.
+        let unresolved = StatementType::Comparison(comparison(
+            FrontMatterType::VARIABLE("undefined".to_string()),
+            OperatorType::Equal,
+            FrontMatterType::NUMBER(1),
+        ));
+        let expr = LogicalExpression { left: Box::new(bool_value(false)), operator: OperatorType::And, right: Box::new(unresolved) };
+        assert_eq!(expr.partial_eval(&Env::new()), bool_value(false));
+    }
+
+    #[test]
+    fn partial_eval_or_drops_a_literal_false_side() {
+        // `false || <unresolved>` collapses to the unresolved side, left as it was.
+        let unresolved = StatementType::Comparison(comparison(
+            FrontMatterType::VARIABLE("undefined".to_string()),
+            OperatorType::Equal,
+            FrontMatterType::NUMBER(1),
+        ));
+        let expr = LogicalExpression { left: Box::new(bool_value(false)), operator: OperatorType::Or, right: Box::new(unresolved.clone()) };
+        assert_eq!(expr.partial_eval(&Env::new()), unresolved);
+    }
+
+    #[test]
+    fn partial_eval_folds_a_double_negation() {
+        let not_true = StatementType::NotExpression(NotExpression { operand: Box::new(bool_value(true)) });
+        let not_not_true = NotExpression { operand: Box::new(not_true) };
+        assert_eq!(not_not_true.partial_eval(&Env::new()), bool_value(true));
+    }
+
+    #[test]
+    fn undefined_variable_is_a_structured_error_not_a_string() {
+        let comp = comparison(FrontMatterType::VARIABLE("missing".to_string()), OperatorType::Equal, FrontMatterType::NUMBER(1));
+        assert_eq!(comp.evaluate(&Env::new()).err(), Some(EvalError::UndefinedVariable("missing".to_string())));
+    }
+
+    #[test]
+    fn unknown_method_name_is_a_structured_error() {
+        let call = MethodCall {
+            object_name: Box::new(FrontMatterType::STRING("hi".to_string())),
+            method_name: Box::new(FrontMatterType::IDENTIFIER("shout".to_string())),
+            arguments: None,
+        };
+        assert_eq!(call.evaluate(&Env::new()).err(), Some(EvalError::UnsupportedMethod("shout".to_string())));
+    }
+
+    #[test]
+    fn first_on_an_empty_string_is_a_structured_error() {
+        let call = MethodCall {
+            object_name: Box::new(FrontMatterType::STRING("".to_string())),
+            method_name: Box::new(FrontMatterType::IDENTIFIER("first".to_string())),
+            arguments: None,
+        };
+        assert_eq!(call.evaluate(&Env::new()).err(), Some(EvalError::EmptyStringAccess));
+    }
+
+    #[test]
+    fn an_invalid_regex_pattern_is_a_structured_error() {
+        let call = MethodCall {
+            object_name: Box::new(FrontMatterType::STRING("hi".to_string())),
+            method_name: Box::new(FrontMatterType::IDENTIFIER("matches".to_string())),
+            arguments: Some(vec![FrontMatterType::STRING("(".to_string())]),
+        };
+        assert!(matches!(call.evaluate(&Env::new()), Err(EvalError::InvalidRegex { .. })));
+    }
 }
@@ -0,0 +1,215 @@
+use crate::ast::front_matter_type::FrontMatterType;
+use crate::ast::shire_expression::{
+    Comparison, LogicalExpression, MethodCall, NotExpression, OperatorType, StatementType,
+    StringComparison, Value,
+};
+
+/// The result type of a checked Shire condition node, inferred bottom-up by [`StatementType::check`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ValueType {
+    Bool,
+    Str,
+    Number,
+    Date,
+}
+
+/// A `StatementType` tree that has been type-checked once, producing the [`ValueType`] each
+/// node would evaluate to without running it. This feeds `FrontMatterType::expected_type`'s
+/// static inference (see `chunk2-1`); it is a separate, read-only pass and does not change how
+/// `Statement::evaluate` runs — `check` has no `Env` to resolve a `VARIABLE` against, so it
+/// reports `TypeError::UndefinedVariable` for exactly the conditions `evaluate` handles fine at
+/// runtime.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum TypedStatement {
+    Comparison {
+        left: Box<FrontMatterType>,
+        operator: OperatorType,
+        right: Box<FrontMatterType>,
+        ty: ValueType,
+    },
+    StringComparison {
+        variable: String,
+        value: String,
+        ty: ValueType,
+    },
+    LogicalExpression {
+        left: Box<TypedStatement>,
+        operator: OperatorType,
+        right: Box<TypedStatement>,
+        ty: ValueType,
+    },
+    NotExpression {
+        operand: Box<TypedStatement>,
+        ty: ValueType,
+    },
+    MethodCall {
+        method_name: String,
+        ty: ValueType,
+    },
+    Value {
+        ty: ValueType,
+    },
+    /// Statement kinds this pass does not assign a `ValueType` to (raw operators,
+    /// `CaseKeyValue`, `ConditionCase`); kept around unchanged so the checked tree still covers the
+    /// whole `StatementType` surface.
+    Opaque(StatementType),
+}
+
+impl TypedStatement {
+    pub fn ty(&self) -> Option<&ValueType> {
+        match self {
+            TypedStatement::Comparison { ty, .. } => Some(ty),
+            TypedStatement::StringComparison { ty, .. } => Some(ty),
+            TypedStatement::LogicalExpression { ty, .. } => Some(ty),
+            TypedStatement::NotExpression { ty, .. } => Some(ty),
+            TypedStatement::MethodCall { ty, .. } => Some(ty),
+            TypedStatement::Value { ty } => Some(ty),
+            TypedStatement::Opaque(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum TypeError {
+    /// An operand did not have the type the surrounding node required.
+    Mismatch { expected: ValueType, actual: ValueType },
+    /// A method name that `MethodCall` does not know how to type.
+    UnknownMethod(String),
+    /// A `Value` whose `FrontMatterType` has no well-defined `ValueType`.
+    UnsupportedValue(FrontMatterType),
+    /// A `VARIABLE` reference inside a `Value`, which `check` cannot resolve without an
+    /// environment of known variable types.
+    UndefinedVariable(String),
+}
+
+fn method_result_type(method_name: &str) -> Result<ValueType, TypeError> {
+    match method_name {
+        "length" => Ok(ValueType::Number),
+        "trim" | "lowercase" | "uppercase" | "first" | "last" => Ok(ValueType::Str),
+        "isEmpty" | "isNotEmpty" | "contains" | "startsWith" | "endsWith" | "matches" => {
+            Ok(ValueType::Bool)
+        }
+        other => Err(TypeError::UnknownMethod(other.to_string())),
+    }
+}
+
+fn front_matter_value_type(value: &FrontMatterType) -> Result<ValueType, TypeError> {
+    match value {
+        FrontMatterType::STRING(_) => Ok(ValueType::Str),
+        FrontMatterType::NUMBER(_) => Ok(ValueType::Number),
+        FrontMatterType::DATE(_) => Ok(ValueType::Date),
+        FrontMatterType::BOOLEAN(_) => Ok(ValueType::Bool),
+        FrontMatterType::EXPRESSION(inner) => inner.check().and_then(|typed| {
+            typed
+                .ty()
+                .cloned()
+                .ok_or_else(|| TypeError::UnsupportedValue(value.clone()))
+        }),
+        FrontMatterType::VARIABLE(name) => Err(TypeError::UndefinedVariable(name.clone())),
+        other => Err(TypeError::UnsupportedValue(other.clone())),
+    }
+}
+
+fn require(ty: ValueType, expected: ValueType) -> Result<ValueType, TypeError> {
+    if ty == expected {
+        Ok(ty)
+    } else {
+        Err(TypeError::Mismatch { expected, actual: ty })
+    }
+}
+
+impl Comparison {
+    fn check(&self) -> Result<TypedStatement, TypeError> {
+        let _ = front_matter_value_type(&self.left)?;
+        let _ = front_matter_value_type(&self.right)?;
+        Ok(TypedStatement::Comparison {
+            left: self.left.clone(),
+            operator: self.operator.type_.clone(),
+            right: self.right.clone(),
+            ty: ValueType::Bool,
+        })
+    }
+}
+
+impl StringComparison {
+    fn check(&self) -> Result<TypedStatement, TypeError> {
+        Ok(TypedStatement::StringComparison {
+            variable: self.variable.clone(),
+            value: self.value.clone(),
+            ty: ValueType::Bool,
+        })
+    }
+}
+
+impl LogicalExpression {
+    fn check(&self) -> Result<TypedStatement, TypeError> {
+        let left = self.left.check()?;
+        let right = self.right.check()?;
+        let left_ty = left
+            .ty()
+            .cloned()
+            .ok_or_else(|| TypeError::Mismatch { expected: ValueType::Bool, actual: ValueType::Str })?;
+        let right_ty = right
+            .ty()
+            .cloned()
+            .ok_or_else(|| TypeError::Mismatch { expected: ValueType::Bool, actual: ValueType::Str })?;
+        require(left_ty, ValueType::Bool)?;
+        require(right_ty, ValueType::Bool)?;
+        Ok(TypedStatement::LogicalExpression {
+            left: Box::new(left),
+            operator: self.operator.clone(),
+            right: Box::new(right),
+            ty: ValueType::Bool,
+        })
+    }
+}
+
+impl NotExpression {
+    fn check(&self) -> Result<TypedStatement, TypeError> {
+        let operand = self.operand.check()?;
+        let operand_ty = operand
+            .ty()
+            .cloned()
+            .ok_or_else(|| TypeError::Mismatch { expected: ValueType::Bool, actual: ValueType::Str })?;
+        require(operand_ty, ValueType::Bool)?;
+        Ok(TypedStatement::NotExpression {
+            operand: Box::new(operand),
+            ty: ValueType::Bool,
+        })
+    }
+}
+
+impl MethodCall {
+    fn check(&self) -> Result<TypedStatement, TypeError> {
+        let method_name = self.method_name.display();
+        let ty = method_result_type(&method_name)?;
+        Ok(TypedStatement::MethodCall { method_name, ty })
+    }
+}
+
+impl Value {
+    fn check(&self) -> Result<TypedStatement, TypeError> {
+        let ty = front_matter_value_type(&self.value)?;
+        Ok(TypedStatement::Value { ty })
+    }
+}
+
+impl StatementType {
+    /// Walks this condition once and produces a parallel tree where every node is annotated
+    /// with the [`ValueType`] it would evaluate to, so callers can reject malformed Shire
+    /// conditions (e.g. a logical operator applied to a non-bool operand) ahead of time. This
+    /// is a standalone static check consumed by `FrontMatterType::expected_type`; it is not
+    /// wired into `Statement::evaluate`, which still resolves operand types from `Env` at
+    /// runtime via `Box<dyn Any>` downcasts.
+    pub fn check(&self) -> Result<TypedStatement, TypeError> {
+        match self {
+            StatementType::Comparison(comp) => comp.check(),
+            StatementType::StringComparison(comp) => comp.check(),
+            StatementType::LogicalExpression(expr) => expr.check(),
+            StatementType::NotExpression(expr) => expr.check(),
+            StatementType::MethodCall(call) => call.check(),
+            StatementType::Value(val) => val.check(),
+            other => Ok(TypedStatement::Opaque(other.clone())),
+        }
+    }
+}